@@ -12,21 +12,25 @@ use super::{
         ButtplugClientConnectionStateShared, ButtplugClientConnector, ButtplugClientConnectorError,
     },
     device::ButtplugClientDevice,
-    ButtplugClientResult, ButtplugClientEvent,
+    ButtplugClientError, ButtplugClientResult, ButtplugClientEvent,
 };
 use crate::core::{
-    messages::{ButtplugMessageUnion, DeviceList, DeviceMessageInfo},
+    messages::{
+        ButtplugMessageUnion, DeviceList, DeviceMessageInfo, Ping, RequestDeviceList,
+        RequestServerInfo, StartScanning, StopAllDevices, StopScanning,
+    },
 };
 use async_std::{
     future::Future,
     prelude::{FutureExt, StreamExt},
     sync::{channel, Receiver, Sender},
-    task::{Context, Poll, Waker},
+    task::{self, Context, Poll, Waker},
 };
 use core::pin::Pin;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 /// Struct used for waiting on replies from the server.
@@ -39,6 +43,11 @@ use std::{
 pub struct ButtplugClientFutureState<T> {
     reply_msg: Option<T>,
     waker: Option<Waker>,
+    /// Set once [set_reply](Self::set_reply) has run. Lets [Drop] tell the
+    /// difference between a state that was consumed by a finished future
+    /// (reply taken back out by the poll) and one that was dropped while still
+    /// waiting on a reply that will now never arrive.
+    completed: bool,
 }
 
 // For some reason, deriving default above doesn't work, but doing an explicit
@@ -48,6 +57,7 @@ impl<T> Default for ButtplugClientFutureState<T> {
         ButtplugClientFutureState::<T> {
             reply_msg: None,
             waker: None,
+            completed: false,
         }
     }
 }
@@ -70,6 +80,7 @@ impl<T> ButtplugClientFutureState<T> {
         }
 
         self.reply_msg = Some(reply);
+        self.completed = true;
 
         if self.waker.is_some() {
             self.waker.take().unwrap().wake();
@@ -77,6 +88,20 @@ impl<T> ButtplugClientFutureState<T> {
     }
 }
 
+impl<T> Drop for ButtplugClientFutureState<T> {
+    /// Warns when a waker state is dropped before ever receiving a reply.
+    ///
+    /// This is almost always a sign of a lost message: the future holding the
+    /// other end went away before the server (or in-process loop) answered, so
+    /// whoever was awaiting it would have blocked forever. Surfacing it as a
+    /// log line turns the old "quiet deadlock" into something observable.
+    fn drop(&mut self) {
+        if self.reply_msg.is_none() && !self.completed {
+            warn!("ButtplugClientFutureState dropped without a reply being set; a message reply was lost.");
+        }
+    }
+}
+
 /// Shared [ButtplugClientConnectionStatus] type.
 ///
 /// [ButtplugClientConnectionStatus] is made to be shared across futures, and we'll
@@ -117,9 +142,20 @@ impl<T> ButtplugClientFuture<T> {
         self.waker_state.clone()
     }
 
-    // TODO Should we implement drop on this, so it'll yell if its dropping and
-    // the waker didn't fire? otherwise it seems like we could have quiet
-    // deadlocks.
+    /// Awaits the reply, giving up after `timeout` with an error.
+    ///
+    /// Consumes the future and races it against a timer. If the server never
+    /// answers, this resolves to a [ButtplugClientError] instead of hanging
+    /// forever, which is what we want for connector sends that might otherwise
+    /// wedge the caller on an unresponsive server.
+    pub async fn with_timeout(self, timeout: Duration) -> Result<T, ButtplugClientError> {
+        match async_std::future::timeout(timeout, self).await {
+            Ok(reply) => Ok(reply),
+            Err(_) => Err(ButtplugClientError::from(ButtplugClientConnectorError::new(
+                "Timed out waiting for server reply.",
+            ))),
+        }
+    }
 }
 
 impl<T> Future for ButtplugClientFuture<T> {
@@ -168,6 +204,14 @@ pub enum ButtplugClientMessage {
     /// Bundled future should have reply set and waker called when this is
     /// finished.
     Message(ButtplugClientMessageFuturePair),
+    /// Client request to enable or disable automatic reconnection when the
+    /// connector side drops. Off by default; applications opt in through this
+    /// message.
+    SetReconnect(bool),
+    /// Client request to start device scanning on the server.
+    StartScanning(ButtplugClientMessageStateShared),
+    /// Client request to stop device scanning on the server.
+    StopScanning(ButtplugClientMessageStateShared),
 }
 
 pub enum ButtplugClientDeviceEvent {
@@ -180,7 +224,14 @@ enum StreamReturn {
     ConnectorMessage(ButtplugMessageUnion),
     ClientMessage(ButtplugClientMessage),
     DeviceMessage(ButtplugClientMessageFuturePair),
-    Disconnect,
+    /// The ping watchdog timer fired and it's time to check in with the server.
+    Ping,
+    /// The client side hung up (all [ButtplugClient] handles were dropped).
+    /// Nothing left to serve, so the loop exits for good.
+    ClientDisconnect,
+    /// The connector side went away. If reconnection is enabled the loop will
+    /// try to bring the connection back; otherwise it exits.
+    ConnectorDisconnect,
 }
 
 struct ButtplugClientEventLoop {
@@ -192,8 +243,28 @@ struct ButtplugClientEventLoop {
     client_receiver: Receiver<ButtplugClientMessage>,
     connector: Box<dyn ButtplugClientConnector>,
     connector_receiver: Receiver<ButtplugMessageUnion>,
+    /// When true, a connector-side disconnect triggers an automatic reconnect
+    /// (with backoff and device reconciliation) instead of tearing the loop
+    /// down. Defaults to off so existing single-shot behavior is preserved.
+    reconnect: bool,
+    /// Maximum time the server will tolerate between pings before it stops all
+    /// devices on its end. Taken from the `ServerInfo` handshake message. A
+    /// value of zero means the server has pinging disabled and the watchdog
+    /// stays dormant.
+    max_ping_time: Duration,
+    /// Instant the last keepalive ping was sent (or the loop started). The
+    /// watchdog arm measures liveness against this: in a healthy loop we get
+    /// scheduled to send a keepalive every half-interval, so if this is ever
+    /// older than [max_ping_time] the loop itself stalled past the negotiated
+    /// deadline and we stop all devices. Liveness is proven by pings, not by
+    /// how often the application sends device commands.
+    last_ping: Instant,
 }
 
+/// How long the event loop waits for a reply to a request it issues itself
+/// (the handshake, device-list reconciliation) before giving up on it.
+const SERVER_REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
 impl ButtplugClientEventLoop {
     pub async fn wait_for_connector(
         event_sender: Sender<ButtplugClientEvent>,
@@ -219,6 +290,14 @@ impl ButtplugClientEventLoop {
                         }
                         Ok(_) => {
                             info!("Connected!");
+                            // Run the protocol handshake so we learn the
+                            // server's capabilities, most importantly the
+                            // maximum time it will tolerate between pings. A
+                            // zero (or missing) value leaves the watchdog
+                            // dormant, which is what the server asks for when
+                            // it has pinging disabled.
+                            let max_ping_time =
+                                Self::request_server_info(connector.as_mut()).await;
                             let mut waker_state = state.lock().unwrap();
                             waker_state.set_reply(Ok(()));
                             let (device_message_sender, device_message_receiver) = channel(256);
@@ -231,6 +310,9 @@ impl ButtplugClientEventLoop {
                                 client_receiver,
                                 connector_receiver: connector.get_event_receiver(),
                                 connector,
+                                reconnect: false,
+                                max_ping_time,
+                                last_ping: Instant::now(),
                             })
                         }
                     }
@@ -243,6 +325,35 @@ impl ButtplugClientEventLoop {
         }
     }
 
+    /// Runs the `RequestServerInfo`/`ServerInfo` handshake over the connector.
+    ///
+    /// Returns the maximum ping interval the server negotiated, which the event
+    /// loop uses to arm its safety watchdog. If the handshake fails or times
+    /// out we fall back to a zero interval, leaving the watchdog dormant rather
+    /// than tripping it on a server that never answered.
+    async fn request_server_info(connector: &mut dyn ButtplugClientConnector) -> Duration {
+        let fut = ButtplugClientMessageFuture::default();
+        connector
+            .send(
+                &ButtplugMessageUnion::RequestServerInfo(RequestServerInfo::new("Buttplug Client")),
+                &fut.get_state_clone(),
+            )
+            .await;
+        match fut.with_timeout(SERVER_REPLY_TIMEOUT).await {
+            Ok(ButtplugMessageUnion::ServerInfo(info)) => {
+                Duration::from_millis(u64::from(info.max_ping_time()))
+            }
+            Ok(_) => {
+                error!("Handshake got an unexpected reply instead of ServerInfo.");
+                Duration::from_millis(0)
+            }
+            Err(_) => {
+                error!("Timed out waiting for ServerInfo handshake reply.");
+                Duration::from_millis(0)
+            }
+        }
+    }
+
     fn create_client_device(&mut self, info: &DeviceMessageInfo) -> ButtplugClientDevice {
         let (event_sender, event_receiver) = channel(256);
         self.device_event_senders
@@ -279,6 +390,12 @@ impl ButtplugClientEventLoop {
                     .send(ButtplugClientEvent::DeviceRemoved(info.unwrap()))
                     .await;
             }
+            ButtplugMessageUnion::ScanningFinished(_) => {
+                info!("Server finished scanning for devices.");
+                self.event_sender
+                    .send(ButtplugClientEvent::ScanningFinished)
+                    .await;
+            }
             _ => panic!("Got connector message type we don't know how to handle!"),
         }
     }
@@ -288,9 +405,22 @@ impl ButtplugClientEventLoop {
         match msg {
             ButtplugClientMessage::Message(msg_fut) => {
                 debug!("Sending message through connector.");
+                // The loop only dispatches here; it must not block waiting for
+                // the reply or it would stall every other arm. The reply future
+                // is owned and awaited by the client API that built this pair,
+                // so that is where the send timeout belongs -- callers wrap
+                // their await in [ButtplugClientFuture::with_timeout] to avoid
+                // hanging on a server that never answers. The in-loop requests
+                // we originate ourselves (handshake, device-list reconcile) do
+                // apply with_timeout directly, since the loop awaits those.
                 self.connector.send(&msg_fut.0, &msg_fut.1).await;
                 true
             }
+            ButtplugClientMessage::SetReconnect(enabled) => {
+                info!("Setting automatic reconnection mode to {}.", enabled);
+                self.reconnect = enabled;
+                true
+            }
             ButtplugClientMessage::Disconnect(state) => {
                 info!("Client requested disconnect");
                 let mut waker_state = state.lock().unwrap();
@@ -312,6 +442,26 @@ impl ButtplugClientEventLoop {
                 info!("Finised setting waker!");
                 true
             }
+            ButtplugClientMessage::StartScanning(state) => {
+                info!("Client requested scanning start.");
+                self.connector
+                    .send(
+                        &ButtplugMessageUnion::StartScanning(StartScanning::default()),
+                        &state,
+                    )
+                    .await;
+                true
+            }
+            ButtplugClientMessage::StopScanning(state) => {
+                info!("Client requested scanning stop.");
+                self.connector
+                    .send(
+                        &ButtplugMessageUnion::StopScanning(StopScanning::default()),
+                        &state,
+                    )
+                    .await;
+                true
+            }
             ButtplugClientMessage::HandleDeviceList(device_list) => {
                 info!("Handling device list!");
                 for d in &device_list.devices {
@@ -329,6 +479,139 @@ impl ButtplugClientEventLoop {
         }
     }
 
+    /// Reconnects to the server after a connector-side drop.
+    ///
+    /// Retries [connect](ButtplugClientConnector::connect) with exponential
+    /// backoff until it succeeds, refreshes the connector event receiver, then
+    /// reconciles the device list so that handles for devices that survived the
+    /// reconnection keep working. Emits [ButtplugClientEvent::Reconnecting]
+    /// before the first attempt and [ButtplugClientEvent::Reconnected] once the
+    /// connection is back and device reconciliation has run. If the device list
+    /// request fails or times out, the known devices are left as they were and
+    /// [ButtplugClientEvent::Reconnected] is still sent.
+    async fn attempt_reconnect(&mut self) {
+        self.event_sender
+            .send(ButtplugClientEvent::Reconnecting)
+            .await;
+        let mut backoff = Duration::from_millis(500);
+        let max_backoff = Duration::from_secs(30);
+        loop {
+            match self.connector.connect().await {
+                Ok(_) => break,
+                Err(err) => {
+                    error!("Reconnection attempt failed: {}", err.message);
+                    task::sleep(backoff).await;
+                    backoff = std::cmp::min(backoff * 2, max_backoff);
+                }
+            }
+        }
+        self.connector_receiver = self.connector.get_event_receiver();
+        self.reconcile_devices().await;
+        self.last_ping = Instant::now();
+        self.event_sender
+            .send(ButtplugClientEvent::Reconnected)
+            .await;
+    }
+
+    /// Re-requests the device list and reconciles it against our known devices.
+    ///
+    /// Devices that are no longer present get a [DeviceRemoved] event and have
+    /// their plumbing torn down; brand new devices get a [DeviceAdded] event.
+    /// Devices that persisted across the reconnection by index are left alone,
+    /// so their existing handles and event senders stay valid.
+    async fn reconcile_devices(&mut self) {
+        let fut = ButtplugClientMessageFuture::default();
+        self.connector
+            .send(
+                &ButtplugMessageUnion::RequestDeviceList(RequestDeviceList::default()),
+                &fut.get_state_clone(),
+            )
+            .await;
+        let device_list = match fut.with_timeout(SERVER_REPLY_TIMEOUT).await {
+            Ok(ButtplugMessageUnion::DeviceList(device_list)) => device_list,
+            Ok(_) => {
+                error!("Reconnection device list request got an unexpected reply.");
+                return;
+            }
+            Err(_) => {
+                error!("Timed out waiting for device list on reconnect.");
+                return;
+            }
+        };
+        let current: HashSet<u32> = device_list
+            .devices
+            .iter()
+            .map(|d| d.device_index)
+            .collect();
+        // Devices that disappeared while we were gone.
+        let stale: Vec<u32> = self
+            .devices
+            .keys()
+            .filter(|idx| !current.contains(idx))
+            .cloned()
+            .collect();
+        for idx in stale {
+            let info = self.devices.remove(&idx).unwrap();
+            self.device_event_senders.remove(&idx);
+            self.event_sender
+                .send(ButtplugClientEvent::DeviceRemoved(info))
+                .await;
+        }
+        // Devices that are new since the reconnection. Persisting devices are
+        // left untouched so their handles keep working.
+        for d in &device_list.devices {
+            if !self.devices.contains_key(&d.device_index) {
+                let device = self.create_client_device(d);
+                self.devices.insert(d.device_index, d.clone());
+                self.event_sender
+                    .send(ButtplugClientEvent::DeviceAdded(device))
+                    .await;
+            }
+        }
+    }
+
+    /// Runs one iteration of the ping watchdog.
+    ///
+    /// Liveness here is about pings, not device-command frequency: a device
+    /// held at a constant speed is perfectly healthy, so we must not stop it
+    /// just because the application hasn't resent a command. Instead the
+    /// watchdog detects a *stalled loop*. In a healthy loop this arm is
+    /// scheduled to fire every half-interval and send a keepalive [Ping], which
+    /// both satisfies the server and refreshes [last_ping](Self::last_ping). If
+    /// by the time this arm actually gets to run more than the negotiated
+    /// [max_ping_time](Self::max_ping_time) has elapsed since the last keepalive,
+    /// the loop missed a ping cycle -- it was blocked past the deadline -- so we
+    /// enforce the protocol's safety guarantee: stop every device and tear the
+    /// loop down with a [ButtplugClientEvent::PingTimeout]. Returns `false` when
+    /// the loop should exit.
+    async fn handle_ping(&mut self) -> bool {
+        let now = Instant::now();
+        if now.duration_since(self.last_ping) > self.max_ping_time {
+            error!("Ping timeout exceeded, stopping all devices.");
+            let stop_fut = ButtplugClientMessageFuture::default();
+            self.connector
+                .send(
+                    &ButtplugMessageUnion::StopAllDevices(StopAllDevices::default()),
+                    &stop_fut.get_state_clone(),
+                )
+                .await;
+            self.event_sender
+                .send(ButtplugClientEvent::PingTimeout)
+                .await;
+            return false;
+        }
+        debug!("Sending keepalive ping.");
+        let ping_fut = ButtplugClientMessageFuture::default();
+        self.connector
+            .send(
+                &ButtplugMessageUnion::Ping(Ping::default()),
+                &ping_fut.get_state_clone(),
+            )
+            .await;
+        self.last_ping = now;
+        true
+    }
+
     pub async fn run(&mut self) {
         // Once connected, wait for messages from either the client or the
         // connector, and send them the direction they're supposed to go.
@@ -336,11 +619,13 @@ impl ButtplugClientEventLoop {
         let mut connector_receiver = self.connector_receiver.clone();
         let mut device_receiver = self.device_message_receiver.clone();
         loop {
+            let ping_time = self.max_ping_time;
+            let last_ping = self.last_ping;
             let client_future = async {
                 match client_receiver.next().await {
                     None => {
                         debug!("Client disconnected.");
-                        StreamReturn::Disconnect
+                        StreamReturn::ClientDisconnect
                     }
                     Some(msg) => StreamReturn::ClientMessage(msg),
                 }
@@ -349,7 +634,7 @@ impl ButtplugClientEventLoop {
                 match connector_receiver.next().await {
                     None => {
                         debug!("Connector disconnected.");
-                        StreamReturn::Disconnect
+                        StreamReturn::ConnectorDisconnect
                     }
                     Some(msg) => StreamReturn::ConnectorMessage(msg),
                 }
@@ -365,8 +650,29 @@ impl ButtplugClientEventLoop {
                     Some(msg) => StreamReturn::DeviceMessage(msg),
                 }
             };
+            // Fire at the next keepalive deadline, which is half a ping
+            // interval after the last keepalive. Sleeping until an *absolute*
+            // deadline (rather than restarting a fresh `ping_time/2` timer on
+            // every loop iteration) means a busy loop that keeps winning other
+            // arms still pings on schedule instead of starving the timer -- and
+            // a loop that's genuinely stalled overshoots the deadline, which is
+            // exactly what the watchdog in `handle_ping` keys off of. When
+            // pinging is disabled we never want this arm to win the race, so
+            // sleep for an effectively infinite duration.
+            let ping_future = async {
+                if ping_time.as_millis() == 0 {
+                    task::sleep(Duration::from_secs(u32::max_value() as u64)).await;
+                } else {
+                    let deadline = last_ping + ping_time / 2;
+                    task::sleep(deadline.saturating_duration_since(Instant::now())).await;
+                }
+                StreamReturn::Ping
+            };
 
-            let stream_fut = event_future.race(client_future).race(device_future);
+            let stream_fut = event_future
+                .race(client_future)
+                .race(device_future)
+                .race(ping_future);
             match stream_fut.await {
                 StreamReturn::ConnectorMessage(msg) => self.parse_connector_message(msg).await,
                 StreamReturn::ClientMessage(msg) => {
@@ -379,10 +685,27 @@ impl ButtplugClientEventLoop {
                     // this device.
                     self.connector.send(&msg_fut.0, &msg_fut.1).await;
                 }
-                StreamReturn::Disconnect => {
-                    info!("Disconnected!");
+                StreamReturn::Ping => {
+                    if !self.handle_ping().await {
+                        break;
+                    }
+                }
+                StreamReturn::ClientDisconnect => {
+                    info!("Client disconnected!");
                     break;
                 }
+                StreamReturn::ConnectorDisconnect => {
+                    if self.reconnect {
+                        info!("Connector disconnected, attempting to reconnect.");
+                        self.attempt_reconnect().await;
+                        // The reconnected connector hands back a fresh event
+                        // receiver, so swap the one we're selecting on.
+                        connector_receiver = self.connector_receiver.clone();
+                    } else {
+                        info!("Disconnected!");
+                        break;
+                    }
+                }
             }
         }
     }
@@ -410,10 +733,12 @@ impl ButtplugClientEventLoop {
 /// connector, or messages from the client, until either server/client
 /// disconnects.
 ///
-/// - Finally, on disconnect, it will tear down, and cannot be used again.
-/// All clients and devices associated with the loop will be invalidated,
-/// and a new [ButtplugClient] (and corresponding
-/// [ButtplugClientInternalLoop]) must be created.
+/// - Finally, on disconnect, it will tear down. By default this invalidates
+/// all clients and devices associated with the loop, and a new
+/// [ButtplugClient] (and corresponding [ButtplugClientInternalLoop]) must be
+/// created. If reconnection is enabled, a connector-side disconnect is instead
+/// handled in-place: the loop reconnects with backoff and reconciles the
+/// device list, keeping handles for devices that persist across the drop.
 ///
 /// # Parameters
 ///
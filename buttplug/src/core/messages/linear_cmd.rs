@@ -0,0 +1,96 @@
+// Buttplug Rust Source Code File - See https://buttplug.io for more info.
+//
+// Copyright 2016-2020 Nonpolynomial Labs LLC. All rights reserved.
+//
+// Licensed under the BSD 3-Clause license. See LICENSE file in the project root
+// for full license information.
+
+use super::*;
+#[cfg(feature = "serialize-json")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serialize-json", derive(Serialize, Deserialize))]
+pub struct LinearSubcommand {
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Index"))]
+  index: u32,
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Position"))]
+  position: f64,
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Duration"))]
+  duration: u32,
+}
+
+impl LinearSubcommand {
+  pub fn new(index: u32, position: f64, duration: u32) -> Self {
+    Self {
+      index,
+      position,
+      duration,
+    }
+  }
+
+  pub fn index(&self) -> u32 {
+    self.index
+  }
+
+  pub fn position(&self) -> f64 {
+    self.position
+  }
+
+  pub fn duration(&self) -> u32 {
+    self.duration
+  }
+}
+
+#[derive(Debug, ButtplugDeviceMessage, PartialEq, Clone)]
+#[cfg_attr(feature = "serialize-json", derive(Serialize, Deserialize))]
+pub struct LinearCmd {
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Id"))]
+  id: u32,
+  #[cfg_attr(feature = "serialize-json", serde(rename = "DeviceIndex"))]
+  device_index: u32,
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Vectors"))]
+  vectors: Vec<LinearSubcommand>,
+}
+
+impl LinearCmd {
+  pub fn new(device_index: u32, vectors: Vec<LinearSubcommand>) -> Self {
+    Self {
+      id: 1,
+      device_index,
+      vectors,
+    }
+  }
+
+  pub fn vectors(&self) -> &Vec<LinearSubcommand> {
+    &self.vectors
+  }
+}
+
+impl ButtplugMessageValidator for LinearCmd {
+  fn is_valid(&self) -> Result<(), ButtplugMessageError> {
+    self.is_not_system_id(self.id)?;
+    // Only the duplicate-index check is possible at the message level. A valid
+    // upper bound for an index comes from the device's linear actuator count,
+    // which this message has no knowledge of, so the in-range validation is
+    // deferred to the device command handling that has the attribute list.
+    let mut seen = HashSet::new();
+    for sub in &self.vectors {
+      if !seen.insert(sub.index) {
+        return Err(ButtplugMessageError::new(&format!(
+          "LinearCmd has a duplicate actuator index {}.",
+          sub.index
+        )));
+      }
+      self.is_in_command_range(
+        sub.position,
+        format!(
+          "LinearCmd Position {} for index {} is invalid. Valid positions are 0.0-1.0.",
+          sub.position, sub.index
+        ),
+      )?;
+    }
+    Ok(())
+  }
+}
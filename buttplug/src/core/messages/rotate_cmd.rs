@@ -0,0 +1,96 @@
+// Buttplug Rust Source Code File - See https://buttplug.io for more info.
+//
+// Copyright 2016-2020 Nonpolynomial Labs LLC. All rights reserved.
+//
+// Licensed under the BSD 3-Clause license. See LICENSE file in the project root
+// for full license information.
+
+use super::*;
+#[cfg(feature = "serialize-json")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serialize-json", derive(Serialize, Deserialize))]
+pub struct RotateSubcommand {
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Index"))]
+  index: u32,
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Speed"))]
+  speed: f64,
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Clockwise"))]
+  clockwise: bool,
+}
+
+impl RotateSubcommand {
+  pub fn new(index: u32, speed: f64, clockwise: bool) -> Self {
+    Self {
+      index,
+      speed,
+      clockwise,
+    }
+  }
+
+  pub fn index(&self) -> u32 {
+    self.index
+  }
+
+  pub fn speed(&self) -> f64 {
+    self.speed
+  }
+
+  pub fn clockwise(&self) -> bool {
+    self.clockwise
+  }
+}
+
+#[derive(Debug, ButtplugDeviceMessage, PartialEq, Clone)]
+#[cfg_attr(feature = "serialize-json", derive(Serialize, Deserialize))]
+pub struct RotateCmd {
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Id"))]
+  id: u32,
+  #[cfg_attr(feature = "serialize-json", serde(rename = "DeviceIndex"))]
+  device_index: u32,
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Rotations"))]
+  rotations: Vec<RotateSubcommand>,
+}
+
+impl RotateCmd {
+  pub fn new(device_index: u32, rotations: Vec<RotateSubcommand>) -> Self {
+    Self {
+      id: 1,
+      device_index,
+      rotations,
+    }
+  }
+
+  pub fn rotations(&self) -> &Vec<RotateSubcommand> {
+    &self.rotations
+  }
+}
+
+impl ButtplugMessageValidator for RotateCmd {
+  fn is_valid(&self) -> Result<(), ButtplugMessageError> {
+    self.is_not_system_id(self.id)?;
+    // Duplicate actuator indices are rejected here. Whether an index actually
+    // addresses a rotator can only be decided against the device's rotation
+    // feature count, which isn't part of the message, so that bounds check
+    // happens in the device command handling rather than here.
+    let mut seen = HashSet::new();
+    for sub in &self.rotations {
+      if !seen.insert(sub.index) {
+        return Err(ButtplugMessageError::new(&format!(
+          "RotateCmd has a duplicate actuator index {}.",
+          sub.index
+        )));
+      }
+      self.is_in_command_range(
+        sub.speed,
+        format!(
+          "RotateCmd Speed {} for index {} is invalid. Valid speeds are 0.0-1.0.",
+          sub.speed, sub.index
+        ),
+      )?;
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,95 @@
+// Buttplug Rust Source Code File - See https://buttplug.io for more info.
+//
+// Copyright 2016-2020 Nonpolynomial Labs LLC. All rights reserved.
+//
+// Licensed under the BSD 3-Clause license. See LICENSE file in the project root
+// for full license information.
+
+use super::*;
+#[cfg(feature = "serialize-json")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serialize-json", derive(Serialize, Deserialize))]
+pub struct VibrateSubcommand {
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Index"))]
+  index: u32,
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Speed"))]
+  speed: f64,
+}
+
+impl VibrateSubcommand {
+  pub fn new(index: u32, speed: f64) -> Self {
+    Self { index, speed }
+  }
+
+  pub fn index(&self) -> u32 {
+    self.index
+  }
+
+  pub fn speed(&self) -> f64 {
+    self.speed
+  }
+}
+
+#[derive(Debug, ButtplugDeviceMessage, PartialEq, Clone)]
+#[cfg_attr(feature = "serialize-json", derive(Serialize, Deserialize))]
+pub struct VibrateCmd {
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Id"))]
+  id: u32,
+  #[cfg_attr(feature = "serialize-json", serde(rename = "DeviceIndex"))]
+  device_index: u32,
+  #[cfg_attr(feature = "serialize-json", serde(rename = "Speeds"))]
+  speeds: Vec<VibrateSubcommand>,
+}
+
+impl VibrateCmd {
+  pub fn new(device_index: u32, speeds: Vec<VibrateSubcommand>) -> Self {
+    Self {
+      id: 1,
+      device_index,
+      speeds,
+    }
+  }
+
+  pub fn speeds(&self) -> &Vec<VibrateSubcommand> {
+    &self.speeds
+  }
+}
+
+impl ButtplugMessageValidator for VibrateCmd {
+  fn is_valid(&self) -> Result<(), ButtplugMessageError> {
+    self.is_not_system_id(self.id)?;
+    // We reject duplicate actuator indices here. The upper bound on an index
+    // depends on how many vibrators a device actually exposes, a count this
+    // message doesn't carry, so the in-range check is left to the device
+    // command handling that knows the attribute list.
+    let mut seen = HashSet::new();
+    for sub in &self.speeds {
+      if !seen.insert(sub.index) {
+        return Err(ButtplugMessageError::new(&format!(
+          "VibrateCmd has a duplicate actuator index {}.",
+          sub.index
+        )));
+      }
+      self.is_in_command_range(
+        sub.speed,
+        format!(
+          "VibrateCmd Speed {} for index {} is invalid. Valid speeds are 0.0-1.0.",
+          sub.speed, sub.index
+        ),
+      )?;
+    }
+    Ok(())
+  }
+}
+
+impl From<SingleMotorVibrateCmd> for VibrateCmd {
+  /// A [SingleMotorVibrateCmd] is just a [VibrateCmd] that drives the device's
+  /// first (and only assumed) actuator, so express it as a single-element
+  /// subcommand list.
+  fn from(msg: SingleMotorVibrateCmd) -> Self {
+    VibrateCmd::new(msg.device_index(), vec![VibrateSubcommand::new(0, msg.speed())])
+  }
+}